@@ -1,22 +1,52 @@
 use bevy::prelude::*;
 use bevy::app::AppExit;
-use std::io::Write;
 use libloading::Library;
-use once_cell::sync::Lazy;
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::path::PathBuf;
+use std::time::SystemTime;
+use std::collections::HashMap;
 
 /// Types for script exports; keep these in sync with the Zig script template.
 type ZigInit = unsafe extern "C" fn();
 type ZigUpdate = unsafe extern "C" fn(f32);
-type ZigUpdateVoid = unsafe extern "C" fn();
+/// Optional export a script may implement to migrate its state across a hot reload
+/// instead of starting over from `zig_init`.
+type ZigReload = unsafe extern "C" fn();
+/// Called exactly once at shutdown. The return value is the script's allocator's leak
+/// count (0 = clean), mirroring `GeneralPurposeAllocator.deinit()`'s check semantics.
+type ZigDeinit = unsafe extern "C" fn() -> u32;
 
-/// Resource that stores the function pointers. We keep the library leaked to ensure the
-/// function pointers remain valid while running the program.
-#[derive(bevy::prelude::Resource)]
+/// Log level ABI shared with the script: 0=err, 1=warn, 2=info, 3=debug (and anything
+/// else falls through to debug). This is the stable contract `zig_set_log_fn` relies on.
+type ZigLogCallback = extern "C" fn(level: u8, msg_ptr: *const u8, msg_len: usize);
+/// Optional export a script may implement to receive a host logging callback; scripts
+/// built without structured logging simply won't export this symbol.
+type ZigSetLogFn = unsafe extern "C" fn(ZigLogCallback);
+
+/// Passed into the script as `zig_set_log_fn`'s argument so `std.log` output is routed
+/// into Bevy's tracing macros instead of going nowhere. The message slice is only valid
+/// for the duration of this call — the script must not retain the pointer.
+extern "C" fn host_log_callback(level: u8, msg_ptr: *const u8, msg_len: usize) {
+    let msg = unsafe { std::slice::from_raw_parts(msg_ptr, msg_len) };
+    let msg = String::from_utf8_lossy(msg);
+    match level {
+        0 => error!("[script] {}", msg),
+        1 => warn!("[script] {}", msg),
+        2 => info!("[script] {}", msg),
+        _ => debug!("[script] {}", msg),
+    }
+}
+
+/// The resolved function pointers for one script. We keep the library leaked to ensure
+/// the pointers remain valid while running the program.
 struct ScriptFns {
     init: ZigInit,
     update: ZigUpdate,
+    reload: Option<ZigReload>,
+    /// Optional export; older scripts built before leak reporting existed simply won't
+    /// have it.
+    deinit: Option<ZigDeinit>,
 }
 
 #[derive(Resource)]
@@ -25,166 +55,519 @@ struct FrameCounter(u32);
 #[derive(Resource)]
 struct MainThreadMarker;
 
+/// Where to find a script library and what symbols to resolve from it. Lets a user
+/// point the host at any compiled Zig library (or rename its entry points) without
+/// recompiling the Rust engine.
+#[derive(Resource, Clone)]
+struct ScriptConfig {
+    /// Directory the compiled library lives in, e.g. `../scripts/zig-script`.
+    base_dir: PathBuf,
+    /// Library name without platform prefix/suffix, e.g. `script` for `libscript.so`.
+    stem: String,
+    init_symbol: String,
+    update_symbol: String,
+    /// Optional export used to install the host logging callback. Missing from older
+    /// scripts that don't use `std.log` yet, which is not treated as an error.
+    log_symbol: String,
+    deinit_symbol: String,
+    auto_build: AutoBuild,
+}
+
+impl Default for ScriptConfig {
+    fn default() -> Self {
+        Self {
+            base_dir: PathBuf::from("../scripts/zig-script"),
+            stem: "script".to_string(),
+            init_symbol: "zig_init".to_string(),
+            update_symbol: "zig_update".to_string(),
+            log_symbol: "zig_set_log_fn".to_string(),
+            deinit_symbol: "zig_deinit".to_string(),
+            auto_build: AutoBuild::Disabled,
+        }
+    }
+}
+
+/// Whether a script load should invoke the Zig compiler itself when the library is
+/// missing or its sources are newer than the compiled artifact.
+#[derive(Clone, Default)]
+enum AutoBuild {
+    #[default]
+    Disabled,
+    /// Run `command` (in `base_dir`) before loading, e.g. `["zig", "build"]` or
+    /// `["sh", "build.sh"]`.
+    Enabled { command: Vec<String> },
+}
+
+impl ScriptConfig {
+    /// Compute the platform-correct library path, e.g. `libscript.so`, `script.dll`,
+    /// or `libscript.dylib` depending on the host OS.
+    fn library_path(&self) -> PathBuf {
+        let filename = format!(
+            "{}{}{}",
+            std::env::consts::DLL_PREFIX,
+            self.stem,
+            std::env::consts::DLL_SUFFIX
+        );
+        self.base_dir.join(filename)
+    }
+
+    /// Run `command` (in `base_dir`) before loading whenever the library is missing or
+    /// its sources are newer than the compiled artifact, e.g.
+    /// `with_auto_build(["sh", "build.sh"])`.
+    fn with_auto_build(mut self, command: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.auto_build = AutoBuild::Enabled {
+            command: command.into_iter().map(Into::into).collect(),
+        };
+        self
+    }
+}
+
+/// One script's live state: its resolved pointers, hot-reload bookkeeping, and its own
+/// reentrancy lock. Keyed by name in `ScriptRegistry` so several scripts can run side by
+/// side without stepping on each other's debounce/mtime state.
+struct ScriptEntry {
+    config: ScriptConfig,
+    fns: ScriptFns,
+    last_mtime: SystemTime,
+    pending_mtime: Option<SystemTime>,
+    /// Libraries loaded by successful hot reloads, oldest first; the last one is the
+    /// library currently in use, and the one loaded at startup is not listed. Every
+    /// library is leaked rather than `dlclose`d, since old function pointers might still
+    /// be in flight on another thread, so all of them stay resident for the remainder of
+    /// the process whether listed here or not.
+    superseded: Vec<&'static Library>,
+    /// Guards every call into this script's code so a hot-reload swap can never race a
+    /// concurrent `zig_update` call.
+    lock: Mutex<()>,
+}
+
+/// Every loaded script, keyed by the name it was registered under.
+#[derive(Resource, Default)]
+struct ScriptRegistry(HashMap<String, ScriptEntry>);
+
+/// Scripts registered via `register_script` before `Startup` has had a chance to load
+/// them into the `ScriptRegistry`.
+#[derive(Resource, Default)]
+struct PendingScripts(Vec<(String, ScriptConfig)>);
+
+/// Tracks whether the shutdown pass calling every script's `zig_deinit` has already run
+/// (one flag for all scripts), so a late-arriving `AppExit` event (Bevy can send more
+/// than one) never calls it twice.
+#[derive(Resource, Default)]
+struct DeinitCalled(bool);
+
+/// Extension trait for registering Zig scripts with a Bevy `App`. This is the
+/// entry point for loading more than one script side by side.
+trait ScriptAppExt {
+    /// Register a script by name, to be loaded at `Startup`. Calling this more than
+    /// once with different names loads several libraries side by side.
+    fn register_script(&mut self, name: impl Into<String>, config: ScriptConfig) -> &mut Self;
+}
+
+impl ScriptAppExt for App {
+    fn register_script(&mut self, name: impl Into<String>, config: ScriptConfig) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(PendingScripts::default)
+            .0
+            .push((name.into(), config));
+        self
+    }
+}
 
 fn main() {
-    println!("[engine] main starting");
-    use std::io::Write;
-    std::io::stdout().flush().ok();
     App::new()
         .add_plugins(MinimalPlugins)
         // Make sure systems requiring `NonSend<MainThreadMarker>` run on the main thread.
         .insert_non_send_resource(MainThreadMarker)
-        .add_systems(Startup, load_script_system)
-        .add_systems(Update, script_update_system)
+        .insert_resource(ScriptRegistry::default())
+        .insert_resource(PendingScripts::default())
+        .insert_resource(DeinitCalled::default())
+        .insert_resource(FrameCounter(0))
+        .add_systems(Startup, load_registered_scripts_system)
+        .add_systems(Update, (script_hot_reload_system, script_update_system).chain())
+        .add_systems(Last, script_deinit_system)
+        .register_script(
+            "default",
+            ScriptConfig::default().with_auto_build(["sh", "build.sh"]),
+        )
         .run();
 }
 
-fn get_script_path() -> PathBuf {
-    PathBuf::from("../scripts/zig-script/libscript.so")
+fn script_mtime(path: &PathBuf) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Bumped for every library load so each generation gets a distinct file name.
+static LIBRARY_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Open `script_path` by `dlopen`-ing a uniquely-named copy rather than the path itself.
+///
+/// `dlopen`/`LoadLibrary` cache by path: if the same path is already mapped in this
+/// process (as it will be after the first load, since we never unload), re-opening it
+/// returns the *existing* handle instead of reading the recompiled file from disk. Hot
+/// reload would silently keep running stale code. Copying to a fresh name each time
+/// forces a real load of the new bytes.
+fn open_unique_copy(script_path: &PathBuf) -> Result<Library, String> {
+    let generation = LIBRARY_GENERATION.fetch_add(1, Ordering::Relaxed);
+    let file_name = script_path
+        .file_name()
+        .ok_or_else(|| format!("{:?} has no file name", script_path))?;
+    let mut unique_name = std::ffi::OsString::from(".");
+    unique_name.push(file_name);
+    unique_name.push(format!(".gen{}", generation));
+    let unique_path = script_path.with_file_name(unique_name);
+
+    std::fs::copy(script_path, &unique_path).map_err(|e| {
+        format!("Failed to stage a private copy of {:?} at {:?}: {}", script_path, unique_path, e)
+    })?;
+
+    let lib = unsafe { Library::new(&unique_path) }
+        .map_err(|e| format!("Failed to load library {:?}: {}", unique_path, e));
+
+    // Best-effort cleanup: once dlopen has mapped the file, its directory entry can be
+    // removed immediately (the mapping stays alive until the process exits, since we
+    // never unload) so successive reloads don't litter the script directory.
+    let _ = std::fs::remove_file(&unique_path);
+
+    lib
+}
+
+/// Newest mtime among `.zig` files under `dir`, searched recursively.
+fn newest_source_mtime(dir: &PathBuf) -> Option<SystemTime> {
+    let mut newest: Option<SystemTime> = None;
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        let candidate = if path.is_dir() {
+            newest_source_mtime(&path)
+        } else if path.extension().is_some_and(|ext| ext == "zig") {
+            script_mtime(&path)
+        } else {
+            None
+        };
+        newest = match (newest, candidate) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, None) => a,
+            (None, b) => b,
+        };
+    }
+    newest
+}
+
+/// True if the library is missing, or any `.zig` source under `base_dir` is newer than it.
+fn needs_build(config: &ScriptConfig) -> bool {
+    match script_mtime(&config.library_path()) {
+        None => true,
+        Some(lib_mtime) => newest_source_mtime(&config.base_dir)
+            .is_some_and(|src_mtime| src_mtime > lib_mtime),
+    }
+}
+
+/// Shell out to the configured build command in `base_dir`, capturing its output.
+/// Compile errors are the caller's to log; on failure this returns the exit status and
+/// the captured stdout/stderr as the error without logging the failure itself.
+fn run_auto_build(config: &ScriptConfig, command: &[String]) -> Result<(), String> {
+    let Some((program, args)) = command.split_first() else {
+        return Err("AutoBuild command is empty".to_string());
+    };
+    info!("Building script in {:?}: {}", config.base_dir, command.join(" "));
+    let output = std::process::Command::new(program)
+        .args(args)
+        .current_dir(&config.base_dir)
+        .output()
+        .map_err(|e| format!("Failed to spawn build command {:?}: {}", command, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Build failed ({}):\nstdout:\n{}\nstderr:\n{}",
+            output.status,
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(())
 }
 
-/// Startup system: load the script and insert function pointers as a resource.
-fn load_script_system(mut commands: Commands) {
-    let script_path = get_script_path();
+/// Resolve `zig_init`/`zig_update` (required, using the names configured in
+/// `ScriptConfig`) and `zig_reload`/`zig_deinit` (both optional) out of a freshly
+/// loaded, leaked library.
+unsafe fn resolve_script_fns(lib: &'static Library, config: &ScriptConfig) -> Result<ScriptFns, String> {
+    let init_sym: libloading::Symbol<'static, ZigInit> = lib
+        .get(format!("{}\0", config.init_symbol).as_bytes())
+        .map_err(|e| format!("Failed to find symbol {}: {}", config.init_symbol, e))?;
+    let update_sym: libloading::Symbol<'static, ZigUpdate> = lib
+        .get(format!("{}\0", config.update_symbol).as_bytes())
+        .map_err(|e| format!("Failed to find symbol {}: {}", config.update_symbol, e))?;
+    let reload = lib
+        .get::<ZigReload>(b"zig_reload")
+        .ok()
+        .map(|sym| *sym);
+    let deinit = lib
+        .get::<ZigDeinit>(format!("{}\0", config.deinit_symbol).as_bytes())
+        .ok()
+        .map(|sym| *sym);
+    Ok(ScriptFns {
+        init: *init_sym,
+        update: *update_sym,
+        reload,
+        deinit,
+    })
+}
+
+/// Install the host logging callback if the script exports `zig_set_log_fn`. Absence of
+/// the symbol is not an error — it just means the script predates structured logging.
+unsafe fn install_log_callback(lib: &Library, config: &ScriptConfig) {
+    if let Ok(set_log_fn) = lib.get::<ZigSetLogFn>(format!("{}\0", config.log_symbol).as_bytes()) {
+        (set_log_fn)(host_log_callback);
+    }
+}
+
+/// Build (if configured to) and load a single script, returning its live `ScriptEntry`.
+unsafe fn load_one_script(name: &str, config: &ScriptConfig) -> Result<ScriptEntry, String> {
+    if let AutoBuild::Enabled { command } = &config.auto_build {
+        if needs_build(config) {
+            run_auto_build(config, command)?;
+        }
+    }
+
+    let script_path = config.library_path();
     if !script_path.exists() {
-        error!("Script library not found at {:?}. Build the script first:", script_path);
-        error!("  cd scripts/zig-script && sh build.sh");
-        return;
+        return Err(format!(
+            "Script library not found at {:?}. Build the script first, e.g.: cd {:?} && sh build.sh",
+            script_path, config.base_dir
+        ));
     }
 
-    println!("[engine] startup system entered");
-    std::io::stdout().flush().ok();
-    unsafe {
-        println!("[engine] attempting to load library at {:?}", script_path);
-        std::io::stdout().flush().ok();
-        match Library::new(&script_path) {
-            Ok(lib) => {
-                println!("[engine] Loaded library: {:?}", script_path);
-
-                // Leak the library so it is not dropped for the lifetime of the program.
-                // This keeps function pointers valid. This is a cheap prototype approach.
-                let boxed = Box::new(lib);
-                // Safety: We leak intentionally to avoid Send/Sync requirements on Library.
-                let static_lib: &'static Library = Box::leak(boxed);
-
-                let init_sym: libloading::Symbol<'static, ZigInit> =
-                    match static_lib.get(b"zig_init") {
-                        Ok(s) => s,
-                        Err(e) => {
-                            error!("Failed to find symbol zig_init: {}", e);
-                            return;
-                        }
-                    };
+    let lib = open_unique_copy(&script_path)?;
+    // Safety: We leak intentionally to avoid Send/Sync requirements on Library and to
+    // keep function pointers valid for the lifetime of the program.
+    let static_lib: &'static Library = Box::leak(Box::new(lib));
 
-                let update_sym: libloading::Symbol<'static, ZigUpdate> =
-                    match static_lib.get(b"zig_update") {
-                        Ok(s) => s,
-                        Err(e) => {
-                            error!("Failed to find symbol zig_update: {}", e);
-                            return;
-                        }
-                    };
-
-                // deref to raw fn pointers
-                let init_fn: ZigInit = *init_sym;
-                let update_fn: ZigUpdate = *update_sym;
-
-                println!("[engine] calling zig_init");
-                std::io::stdout().flush().ok();
-                init_fn();
-                println!("[engine] zig_init returned");
-                std::io::stdout().flush().ok();
-
-                commands.insert_resource(ScriptFns {
-                    init: init_fn,
-                    update: update_fn,
-                });
-                // Add a counter resource to stop after a few frames for the prototype.
-                commands.insert_resource(FrameCounter(0));
-                println!("[engine] calling zig_update from startup system");
-                std::io::stdout().flush().ok();
-                (update_fn)(1.0 / 60.0);
-                println!("[engine] returned from zig_update in startup");
-                std::io::stdout().flush().ok();
-            }
-            Err(e) => {
-                error!("Failed to load library {:?}: {}", script_path, e);
+    let fns = resolve_script_fns(static_lib, config)?;
+    install_log_callback(static_lib, config);
+
+    (fns.init)();
+    info!("Loaded script '{}' from {:?}", name, script_path);
+
+    let last_mtime = script_mtime(&script_path).unwrap_or_else(SystemTime::now);
+
+    Ok(ScriptEntry {
+        config: config.clone(),
+        fns,
+        last_mtime,
+        pending_mtime: None,
+        superseded: Vec::new(),
+        lock: Mutex::new(()),
+    })
+}
+
+/// Startup system: load every script registered via `register_script` into the registry.
+fn load_registered_scripts_system(mut pending: ResMut<PendingScripts>, mut registry: ResMut<ScriptRegistry>) {
+    for (name, config) in pending.0.drain(..) {
+        match unsafe { load_one_script(&name, &config) } {
+            Ok(entry) => {
+                registry.0.insert(name, entry);
             }
+            Err(e) => error!("Failed to load script '{}': {}", name, e),
         }
     }
 }
 
-/// Per-frame system: call the Zig update function using Bevy's Time dt.
-fn script_update_system(script_fns: Option<Res<ScriptFns>>, _marker: NonSend<MainThreadMarker>, mut counter: Option<ResMut<FrameCounter>>, mut exit: EventWriter<AppExit>) {
-    if let Some(fns_res) = script_fns {
-        let fns: &ScriptFns = &*fns_res;
-        // Be careful, calling into script may throw panics â€” wrap in `unsafe`.
-        unsafe {
-            println!("[engine] About to call the Zig update (fn pointer)");
-            // Guard with a mutex to prevent possible re-entrancy / concurrent calls into native code.
-            static UPDATE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
-            let _guard = UPDATE_LOCK.lock().unwrap();
-            println!("[engine] update fn pointer = {:#x}", fns.update as usize);
-            // Try calling the void variant first from a fresh load as a cross-check.
-            let lib_path = get_script_path();
-            if let Ok(lib2) = libloading::Library::new(&lib_path) {
-                if let Ok(sym2) = lib2.get::<ZigUpdateVoid>(b"zig_update_void") {
-                    let f2: ZigUpdateVoid = *sym2;
-                    println!("[engine] About to call the Zig update (void, fresh load)");
-                    f2();
-                    println!("[engine] Called the Zig update (void, fresh load)");
-                } else {
-                    println!("[engine] Failed to locate zig_update_void in fresh load");
-                }
-            } else {
-                println!("[engine] Failed to fresh load the library for void test");
-            }
-            (fns.update)(1.0 / 60.0);
-            println!("[engine] Called the Zig update (fn pointer)");
-
-            // As a test: attempt to load the library on-demand and call its `zig_update` symbol.
-            // This helps determine whether storing the function pointer is the issue.
-            let lib_path = get_script_path();
-            match libloading::Library::new(&lib_path) {
-                Ok(lib) => {
-                    match lib.get::<ZigUpdate>(b"zig_update") {
-                        Ok(sym) => {
-                            let f: ZigUpdate = *sym;
-                            println!("[engine] About to call the Zig update (fresh load)");
-                            f(1.0 / 60.0);
-                            println!("[engine] Called the Zig update (fresh load)");
+/// Per-frame system: for each registered script whose `.so` has changed on disk, load a
+/// fresh copy, resolve its symbols, and atomically swap its `ScriptFns` under its own
+/// lock. Changes are debounced by one frame so we don't reload a half-written file
+/// mid-compile.
+fn script_hot_reload_system(mut registry: ResMut<ScriptRegistry>) {
+    for (name, entry) in registry.0.iter_mut() {
+        let script_path = entry.config.library_path();
+        let Some(current_mtime) = script_mtime(&script_path) else {
+            continue;
+        };
+
+        match entry.pending_mtime {
+            // Saw this mtime last frame too: it's stable, go ahead and reload.
+            Some(pending) if pending == current_mtime && current_mtime != entry.last_mtime => {
+                entry.pending_mtime = None;
+                unsafe {
+                    let _guard = entry.lock.lock().unwrap();
+
+                    match open_unique_copy(&script_path) {
+                        Ok(lib) => {
+                            let static_lib: &'static Library = Box::leak(Box::new(lib));
+                            match resolve_script_fns(static_lib, &entry.config) {
+                                Ok(new_fns) => {
+                                    info!("Hot-reloading script '{}' at {:?}", name, script_path);
+                                    install_log_callback(static_lib, &entry.config);
+                                    // Prefer the script's own migration path if it has
+                                    // one; otherwise fall back to a fresh `zig_init`.
+                                    match new_fns.reload {
+                                        Some(reload) => reload(),
+                                        None => (new_fns.init)(),
+                                    }
+                                    entry.fns = new_fns;
+                                    entry.last_mtime = current_mtime;
+                                    entry.superseded.push(static_lib);
+                                }
+                                Err(e) => {
+                                    error!("Hot reload of '{}' failed, keeping previous script: {}", name, e);
+                                }
+                            }
                         }
                         Err(e) => {
-                            println!("[engine] Failed to get zig_update symbol from fresh load: {}", e);
+                            error!("Hot reload of '{}' failed to open {:?}: {}", name, script_path, e);
                         }
                     }
                 }
-                Err(e) => {
-                    println!("[engine] Fresh library load failed: {}", e);
-                }
             }
-            // Attempt a void update call variant as well
-            match libloading::Library::new(&lib_path) {
-                Ok(lib2) => {
-                    match lib2.get::<ZigUpdateVoid>(b"zig_update_void") {
-                        Ok(sym2) => {
-                            let f2: ZigUpdateVoid = *sym2;
-                            println!("[engine] About to call the Zig update (void, fresh load)");
-                            f2();
-                            println!("[engine] Called the Zig update (void, fresh load)");
-                        }
-                        Err(e) => println!("[engine] Failed to find zig_update_void: {}", e),
-                    }
+            // New (or still-changing) mtime: remember it and wait one more frame.
+            _ => {
+                if current_mtime != entry.last_mtime {
+                    entry.pending_mtime = Some(current_mtime);
                 }
-                Err(e) => println!("[engine] Fresh lib load 2 failed: {}", e),
             }
         }
     }
-    if let Some(mut c) = counter {
-        c.0 += 1;
-        if c.0 > 10 {
-            info!("Reached frame limit, quitting.");
-            exit.send(AppExit);
+}
+
+/// Per-frame system: call every registered script's `zig_update(dt)` under its own
+/// reentrancy lock, passing a fixed prototype timestep of 1/60 s as `dt`.
+fn script_update_system(
+    registry: Res<ScriptRegistry>,
+    _marker: NonSend<MainThreadMarker>,
+    mut counter: ResMut<FrameCounter>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for entry in registry.0.values() {
+        // Be careful, calling into script may throw panics — wrap in `unsafe`.
+        unsafe {
+            let _guard = entry.lock.lock().unwrap();
+            (entry.fns.update)(1.0 / 60.0);
         }
     }
+
+    counter.0 += 1;
+    if counter.0 > 10 {
+        info!("Reached frame limit, quitting.");
+        exit.send(AppExit);
+    }
+}
+
+/// `Last`-schedule system: on the app's first `AppExit` event, call each script's
+/// `zig_deinit` exactly once under its own lock and log its leak count. Gives script
+/// authors a defined place to free resources, and surfaces leaks in each script's own
+/// allocator at shutdown.
+fn script_deinit_system(
+    mut exit_events: EventReader<AppExit>,
+    mut registry: ResMut<ScriptRegistry>,
+    mut called: ResMut<DeinitCalled>,
+) {
+    if called.0 || exit_events.read().next().is_none() {
+        return;
+    }
+    called.0 = true;
+
+    for (name, entry) in registry.0.iter_mut() {
+        let Some(deinit) = entry.fns.deinit else {
+            info!("Script '{}' doesn't export zig_deinit; skipping leak reporting", name);
+            continue;
+        };
+        unsafe {
+            let _guard = entry.lock.lock().unwrap();
+            let leaks = deinit();
+            if leaks != 0 {
+                error!("Script '{}' leaked {} allocation(s) at shutdown", name, leaks);
+            } else {
+                info!("Script '{}' deinitialized cleanly (0 leaks)", name);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh scratch directory under the OS temp dir, unique per test run.
+    fn scratch_dir(label: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "bevy-zig-scripting-test-{}-{}-{:?}",
+            label,
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn library_path_uses_platform_prefix_and_suffix() {
+        let config = ScriptConfig {
+            base_dir: PathBuf::from("/scripts/zig-script"),
+            stem: "script".to_string(),
+            ..ScriptConfig::default()
+        };
+        let expected = format!(
+            "{}script{}",
+            std::env::consts::DLL_PREFIX,
+            std::env::consts::DLL_SUFFIX
+        );
+        assert_eq!(config.library_path(), PathBuf::from("/scripts/zig-script").join(expected));
+    }
+
+    #[test]
+    fn needs_build_when_library_missing() {
+        let dir = scratch_dir("missing-lib");
+        let config = ScriptConfig { base_dir: dir.clone(), ..ScriptConfig::default() };
+
+        assert!(needs_build(&config));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn needs_build_false_when_library_newer_than_sources() {
+        let dir = scratch_dir("fresh-lib");
+        std::fs::write(dir.join("main.zig"), b"// source").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let config = ScriptConfig { base_dir: dir.clone(), ..ScriptConfig::default() };
+        std::fs::write(config.library_path(), b"fake compiled output").unwrap();
+
+        assert!(!needs_build(&config));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn needs_build_true_when_source_newer_than_library() {
+        let dir = scratch_dir("stale-lib");
+        let config = ScriptConfig { base_dir: dir.clone(), ..ScriptConfig::default() };
+        std::fs::write(config.library_path(), b"fake compiled output").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::write(dir.join("main.zig"), b"// updated source").unwrap();
+
+        assert!(needs_build(&config));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn newest_source_mtime_recurses_into_subdirectories() {
+        let dir = scratch_dir("nested-src");
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("sub").join("nested.zig"), b"// nested").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"ignored, not a .zig file").unwrap();
+
+        assert!(newest_source_mtime(&dir).is_some());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn newest_source_mtime_ignores_non_zig_files() {
+        let dir = scratch_dir("non-zig-only");
+        std::fs::write(dir.join("README.md"), b"not a script").unwrap();
+
+        assert!(newest_source_mtime(&dir).is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }